@@ -1,6 +1,6 @@
 use chinchilib::pixels::Pixels;
 use chinchilib::rgb;
-use chinchilib::{put_pixel, GfxApp, Key, WinitHandler};
+use chinchilib::{put_pixel, GfxApp, Key, WindowHandle, WinitHandler};
 
 fn main() {
     env_logger::init();
@@ -39,7 +39,11 @@ const RED: rgb::RGBA8 = rgb::RGBA8 {
 };
 
 impl GfxApp for MovingPixel {
-    fn on_tick(&mut self, pressed_keys: &std::collections::HashSet<Key>) -> bool {
+    fn on_tick(
+        &mut self,
+        pressed_keys: &std::collections::HashSet<Key>,
+        _window: &mut WindowHandle,
+    ) -> bool {
         let mut needs_redraw = true;
         for key in pressed_keys {
             match key {
@@ -63,7 +67,7 @@ impl GfxApp for MovingPixel {
         needs_redraw
     }
 
-    fn draw(&mut self, pixels: &mut Pixels, width: usize) {
+    fn draw(&self, pixels: &mut Pixels, width: usize) {
         if self.pos.0 * self.pos.1 < pixels.frame().len() {
             put_pixel(pixels.frame_mut(), width, self.pos.0, self.pos.1, RED);
         }
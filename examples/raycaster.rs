@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use chinchilib::pixels::Pixels;
+use chinchilib::raycast::{Heading, World};
+use chinchilib::{DoneStatus, GfxApp, Key, WindowHandle, WinitHandler};
+
+const WIDTH: usize = 320;
+const HEIGHT: usize = 240;
+
+fn main() {
+    env_logger::init();
+
+    let raycaster = Box::new(Raycaster::default());
+    let mut app = WinitHandler::new(raycaster, (WIDTH, HEIGHT), 60);
+    app.run().unwrap();
+}
+
+/// Minimal app wiring [`World::render`] up to a window: arrow keys strafe and move the player
+/// forward/backward, `,`/`.` pan the view left/right.
+struct Raycaster {
+    world: World,
+}
+
+impl Default for Raycaster {
+    fn default() -> Self {
+        Self {
+            world: World::default(),
+        }
+    }
+}
+
+impl GfxApp for Raycaster {
+    fn on_tick(&mut self, pressed_keys: &HashSet<Key>, _window: &mut WindowHandle) -> bool {
+        let mut needs_redraw = false;
+        for key in pressed_keys {
+            match key {
+                Key::Up => {
+                    self.world.move_player(Heading::Forward);
+                    needs_redraw = true;
+                }
+                Key::Down => {
+                    self.world.move_player(Heading::Backward);
+                    needs_redraw = true;
+                }
+                Key::Left => {
+                    self.world.move_player(Heading::Left);
+                    needs_redraw = true;
+                }
+                Key::Right => {
+                    self.world.move_player(Heading::Right);
+                    needs_redraw = true;
+                }
+                Key::Comma => {
+                    self.world.pan_left();
+                    needs_redraw = true;
+                }
+                Key::Period => {
+                    self.world.pan_right();
+                    needs_redraw = true;
+                }
+                _ => {}
+            }
+        }
+        needs_redraw
+    }
+
+    fn draw(&self, pixels: &mut Pixels, width: usize) {
+        self.world.render(pixels, width, HEIGHT);
+    }
+
+    fn done(&self) -> DoneStatus {
+        DoneStatus::NotDone
+    }
+}
@@ -0,0 +1,335 @@
+//! Layout-independent keyboard input.
+//!
+//! `MyKeys` used to be matched against winit's *logical* `Key`, which is why it only ever covered
+//! an AZERTY-centric handful of keys: the logical key reflects whatever glyph the layout puts on a
+//! keycap, so "the key left of the spacebar" isn't the same logical key on AZERTY and QWERTY.
+//! [`Key`] instead comes from winit's *physical* `KeyCode`, which identifies the key by its
+//! position on the board regardless of layout, and covers the full set games typically need:
+//! letters, digits, function keys, punctuation, and common modifiers/editing keys.
+//!
+//! [`KeyMap`] lets an app bind those physical keys to its own `Action` type, either directly with
+//! [`KeyMap::bind`] or by parsing an accelerator-style string such as `"Left"`, `"Space"`, `"a"`,
+//! `"F5"`, or `","` with [`KeyMap::bind_str`]. Keys with no explicit binding still resolve via
+//! `Action::from(key)`, so `WinitHandler<Key>` (the default) works out of the box with no keymap at
+//! all.
+
+use std::collections::HashMap;
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+/// A physical key, identified by its position on the board rather than the glyph a particular
+/// keyboard layout happens to put on it.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+#[rustfmt::skip]
+pub enum Key {
+    Up, Down, Left, Right,
+    A, B, C, D, E, F, G, H, I, J, K, L, M,
+    N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Digit0, Digit1, Digit2, Digit3, Digit4,
+    Digit5, Digit6, Digit7, Digit8, Digit9,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24,
+    Space, Enter, Tab, Escape, Backspace,
+    Shift, Control, Alt, Super,
+    Comma, Period, Minus, Equal, Semicolon, Slash, Backslash,
+    BracketLeft, BracketRight, Quote, Backquote,
+}
+
+impl std::convert::TryFrom<PhysicalKey> for Key {
+    type Error = ();
+
+    fn try_from(value: PhysicalKey) -> Result<Self, ()> {
+        let PhysicalKey::Code(code) = value else {
+            return Err(());
+        };
+        use Key::*;
+        Ok(match code {
+            KeyCode::ArrowUp => Up,
+            KeyCode::ArrowDown => Down,
+            KeyCode::ArrowLeft => Left,
+            KeyCode::ArrowRight => Right,
+            KeyCode::KeyA => A,
+            KeyCode::KeyB => B,
+            KeyCode::KeyC => C,
+            KeyCode::KeyD => D,
+            KeyCode::KeyE => E,
+            KeyCode::KeyF => F,
+            KeyCode::KeyG => G,
+            KeyCode::KeyH => H,
+            KeyCode::KeyI => I,
+            KeyCode::KeyJ => J,
+            KeyCode::KeyK => K,
+            KeyCode::KeyL => L,
+            KeyCode::KeyM => M,
+            KeyCode::KeyN => N,
+            KeyCode::KeyO => O,
+            KeyCode::KeyP => P,
+            KeyCode::KeyQ => Q,
+            KeyCode::KeyR => R,
+            KeyCode::KeyS => S,
+            KeyCode::KeyT => T,
+            KeyCode::KeyU => U,
+            KeyCode::KeyV => V,
+            KeyCode::KeyW => W,
+            KeyCode::KeyX => X,
+            KeyCode::KeyY => Y,
+            KeyCode::KeyZ => Z,
+            KeyCode::Digit0 => Digit0,
+            KeyCode::Digit1 => Digit1,
+            KeyCode::Digit2 => Digit2,
+            KeyCode::Digit3 => Digit3,
+            KeyCode::Digit4 => Digit4,
+            KeyCode::Digit5 => Digit5,
+            KeyCode::Digit6 => Digit6,
+            KeyCode::Digit7 => Digit7,
+            KeyCode::Digit8 => Digit8,
+            KeyCode::Digit9 => Digit9,
+            KeyCode::F1 => F1,
+            KeyCode::F2 => F2,
+            KeyCode::F3 => F3,
+            KeyCode::F4 => F4,
+            KeyCode::F5 => F5,
+            KeyCode::F6 => F6,
+            KeyCode::F7 => F7,
+            KeyCode::F8 => F8,
+            KeyCode::F9 => F9,
+            KeyCode::F10 => F10,
+            KeyCode::F11 => F11,
+            KeyCode::F12 => F12,
+            KeyCode::F13 => F13,
+            KeyCode::F14 => F14,
+            KeyCode::F15 => F15,
+            KeyCode::F16 => F16,
+            KeyCode::F17 => F17,
+            KeyCode::F18 => F18,
+            KeyCode::F19 => F19,
+            KeyCode::F20 => F20,
+            KeyCode::F21 => F21,
+            KeyCode::F22 => F22,
+            KeyCode::F23 => F23,
+            KeyCode::F24 => F24,
+            KeyCode::Space => Space,
+            KeyCode::Enter => Enter,
+            KeyCode::Tab => Tab,
+            KeyCode::Escape => Escape,
+            KeyCode::Backspace => Backspace,
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => Shift,
+            KeyCode::ControlLeft | KeyCode::ControlRight => Control,
+            KeyCode::AltLeft | KeyCode::AltRight => Alt,
+            KeyCode::SuperLeft | KeyCode::SuperRight => Super,
+            KeyCode::Comma => Comma,
+            KeyCode::Period => Period,
+            KeyCode::Minus => Minus,
+            KeyCode::Equal => Equal,
+            KeyCode::Semicolon => Semicolon,
+            KeyCode::Slash => Slash,
+            KeyCode::Backslash => Backslash,
+            KeyCode::BracketLeft => BracketLeft,
+            KeyCode::BracketRight => BracketRight,
+            KeyCode::Quote => Quote,
+            KeyCode::Backquote => Backquote,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// A binding string didn't match any recognized [`Key`], e.g. a typo in a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyParseError(String);
+
+impl std::fmt::Display for KeyParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unrecognized key binding {:?}", self.0)
+    }
+}
+
+impl std::error::Error for KeyParseError {}
+
+impl std::str::FromStr for Key {
+    type Err = KeyParseError;
+
+    /// Parse an accelerator-style binding such as `"Left"`, `"Space"`, `"a"`, `"F5"`, or a lone
+    /// punctuation character like `","`, `"-"`, `"="`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        use Key::*;
+        let named = match name {
+            "Up" | "ArrowUp" => Up,
+            "Down" | "ArrowDown" => Down,
+            "Left" | "ArrowLeft" => Left,
+            "Right" | "ArrowRight" => Right,
+            "Space" => Space,
+            "Enter" | "Return" => Enter,
+            "Tab" => Tab,
+            "Escape" | "Esc" => Escape,
+            "Backspace" => Backspace,
+            "Shift" => Shift,
+            "Control" | "Ctrl" => Control,
+            "Alt" => Alt,
+            "Super" | "Meta" => Super,
+            "," => Comma,
+            "." => Period,
+            "-" => Minus,
+            "=" => Equal,
+            ";" => Semicolon,
+            "/" => Slash,
+            "\\" => Backslash,
+            "[" => BracketLeft,
+            "]" => BracketRight,
+            "'" => Quote,
+            "`" => Backquote,
+            "F1" => F1,
+            "F2" => F2,
+            "F3" => F3,
+            "F4" => F4,
+            "F5" => F5,
+            "F6" => F6,
+            "F7" => F7,
+            "F8" => F8,
+            "F9" => F9,
+            "F10" => F10,
+            "F11" => F11,
+            "F12" => F12,
+            "F13" => F13,
+            "F14" => F14,
+            "F15" => F15,
+            "F16" => F16,
+            "F17" => F17,
+            "F18" => F18,
+            "F19" => F19,
+            "F20" => F20,
+            "F21" => F21,
+            "F22" => F22,
+            "F23" => F23,
+            "F24" => F24,
+            _ if name.len() == 1 => match name.chars().next().unwrap().to_ascii_uppercase() {
+                'A' => A,
+                'B' => B,
+                'C' => C,
+                'D' => D,
+                'E' => E,
+                'F' => F,
+                'G' => G,
+                'H' => H,
+                'I' => I,
+                'J' => J,
+                'K' => K,
+                'L' => L,
+                'M' => M,
+                'N' => N,
+                'O' => O,
+                'P' => P,
+                'Q' => Q,
+                'R' => R,
+                'S' => S,
+                'T' => T,
+                'U' => U,
+                'V' => V,
+                'W' => W,
+                'X' => X,
+                'Y' => Y,
+                'Z' => Z,
+                '0' => Digit0,
+                '1' => Digit1,
+                '2' => Digit2,
+                '3' => Digit3,
+                '4' => Digit4,
+                '5' => Digit5,
+                '6' => Digit6,
+                '7' => Digit7,
+                '8' => Digit8,
+                '9' => Digit9,
+                _ => return Err(KeyParseError(name.to_string())),
+            },
+            _ => return Err(KeyParseError(name.to_string())),
+        };
+        Ok(named)
+    }
+}
+
+/// A configurable mapping from physical [`Key`]s to an app-defined `Action`.
+///
+/// Keys with no binding registered fall back to `Action::from(key)`, so `KeyMap::<Key>::new()`
+/// (the default for `WinitHandler`) needs no bindings at all: the resolved action is just the key
+/// itself. Real apps register bindings before calling `WinitHandler::run`, either with
+/// [`KeyMap::bind`] for a concrete [`Key`], or [`KeyMap::bind_str`] to parse one from a
+/// config-friendly accelerator string.
+pub struct KeyMap<Action> {
+    bindings: HashMap<Key, Action>,
+}
+
+impl<Action> Default for KeyMap<Action> {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+}
+
+impl<Action: Clone + From<Key>> KeyMap<Action> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind a physical key to an action, overriding `Action::from(key)` for that key.
+    pub fn bind(&mut self, key: Key, action: Action) -> &mut Self {
+        self.bindings.insert(key, action);
+        self
+    }
+
+    /// Bind an accelerator-style string (e.g. `"Left"`, `"a"`, `"F5"`, `","`) to an action.
+    /// Returns a [`KeyParseError`] if the string isn't a recognized key.
+    pub fn bind_str(&mut self, binding: &str, action: Action) -> Result<&mut Self, KeyParseError> {
+        let key: Key = binding.parse()?;
+        Ok(self.bind(key, action))
+    }
+
+    pub(crate) fn resolve(&self, key: Key) -> Action {
+        self.bindings
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| Action::from(key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_str_named_keys() {
+        assert_eq!("Left".parse(), Ok(Key::Left));
+        assert_eq!("F5".parse(), Ok(Key::F5));
+        assert_eq!(",".parse(), Ok(Key::Comma));
+    }
+
+    #[test]
+    fn from_str_single_char_fallback() {
+        assert_eq!("a".parse(), Ok(Key::A));
+        assert_eq!("Z".parse(), Ok(Key::Z));
+        assert_eq!("7".parse(), Ok(Key::Digit7));
+    }
+
+    #[test]
+    fn from_str_rejects_garbage() {
+        assert_eq!(
+            "NotAKey".parse::<Key>(),
+            Err(KeyParseError("NotAKey".to_string()))
+        );
+        assert_eq!("ab".parse::<Key>(), Err(KeyParseError("ab".to_string())));
+    }
+
+    #[test]
+    fn bind_str_binds_parsed_key() {
+        let mut keymap: KeyMap<Key> = KeyMap::new();
+        keymap.bind_str("Left", Key::A).unwrap();
+        assert_eq!(keymap.resolve(Key::Left), Key::A);
+    }
+
+    #[test]
+    fn bind_str_propagates_parse_error() {
+        let mut keymap: KeyMap<Key> = KeyMap::new();
+        assert!(matches!(
+            keymap.bind_str("NotAKey", Key::A),
+            Err(e) if e == KeyParseError("NotAKey".to_string())
+        ));
+    }
+}
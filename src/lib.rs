@@ -6,48 +6,15 @@ pub use rgb;
 pub use winit;
 use winit::window::{Window, WindowId};
 
-/// Mapping for the keys that are recognized. They are centered an AZERTY keyboard's essential keys
-/// needed for games.
-/// TODO: makes this less centered arround AZERTY
-#[derive(Eq, Hash, PartialEq)]
-pub enum MyKeys {
-    KeyA,
-    KeyZ,
-    KeyE,
-    KeyQ,
-    KeyS,
-    KeyD,
-    Up,
-    Down,
-    Left,
-    Right,
-}
+mod keymap;
+pub use keymap::{Key, KeyMap, KeyParseError};
 
-impl std::convert::TryFrom<&winit::keyboard::Key> for MyKeys {
-    type Error = ();
-    fn try_from(value: &winit::keyboard::Key) -> Result<Self, ()> {
-        use winit::keyboard::{Key, NamedKey};
-        match value {
-            Key::Named(NamedKey::ArrowLeft) => Some(MyKeys::Left),
-            Key::Named(NamedKey::ArrowRight) => Some(MyKeys::Right),
-            Key::Named(NamedKey::ArrowUp) => Some(MyKeys::Up),
-            Key::Named(NamedKey::ArrowDown) => Some(MyKeys::Down),
-            Key::Character(name) if name == "q" => Some(MyKeys::KeyQ),
-            Key::Character(name) if name == "d" => Some(MyKeys::KeyD),
-            Key::Character(name) if name == "z" => Some(MyKeys::KeyZ),
-            Key::Character(name) if name == "s" => Some(MyKeys::KeyS),
-            Key::Character(name) if name == "a" => Some(MyKeys::KeyA),
-            Key::Character(name) if name == "e" => Some(MyKeys::KeyE),
-            _ => None,
-        }
-        .ok_or(())
-    }
-}
+pub mod raycast;
 
 /// Everyting about the window. Pixels and Window are options because they
 /// are constructed on "resume" and cannot be construted earlier
-pub struct WinitHandler {
-    winfbx: Option<WinFbx>,
+pub struct WinitHandler<Action: Eq + std::hash::Hash + Clone + From<Key> + 'static = Key> {
+    winfbx: Option<WinFbx<Action>>,
     width: usize,
     height: usize,
     last_frame: std::time::Instant,
@@ -56,8 +23,21 @@ pub struct WinitHandler {
     /// events. This can be used if you have physics or an animation to run. Defaults to false to
     /// preserve performance.
     always_tick: bool,
-    app: Option<Box<dyn GfxApp>>,
-    cursor_pos: (f64, f64),
+    app: Option<Box<dyn GfxApp<Action>>>,
+    keymap: KeyMap<Action>,
+    /// Lazily created so `.run()` keeps working without ever touching `pump`/`run_on_demand`, and
+    /// reused across `pump` calls so the window survives between them.
+    event_loop: Option<winit::event_loop::EventLoop<()>>,
+}
+
+/// Outcome of a single [`WinitHandler::pump`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PumpStatus {
+    /// Pending events were processed (or none arrived before the timeout); the caller should keep
+    /// pumping.
+    Continue,
+    /// The app asked to exit, carrying the same status code `std::process::exit` would use.
+    Exit(i32),
 }
 
 fn hz_to_nanosec_period(hz: u16) -> u64 {
@@ -74,10 +54,11 @@ mod test {
     }
 }
 
-impl WinitHandler {
+impl<Action: Eq + std::hash::Hash + Clone + From<Key> + 'static> WinitHandler<Action> {
     /// Create a new handler with an app, a window size and a desired tick rate. Run app with
-    /// `.run()`
-    pub fn new(app: Box<dyn GfxApp>, size: (usize, usize), tick_per_second: u16) -> Self {
+    /// `.run()`. Keys resolve to `Action::from(key)` until bindings are registered with
+    /// [`Self::set_keymap`].
+    pub fn new(app: Box<dyn GfxApp<Action>>, size: (usize, usize), tick_per_second: u16) -> Self {
         let nsec_period = hz_to_nanosec_period(tick_per_second);
         Self {
             winfbx: None,
@@ -86,8 +67,9 @@ impl WinitHandler {
             last_frame: std::time::Instant::now(),
             tick: std::time::Duration::from_nanos(nsec_period),
             app: Some(app),
-            cursor_pos: (0.0, 0.0),
             always_tick: false,
+            keymap: KeyMap::new(),
+            event_loop: None,
         }
     }
 
@@ -101,20 +83,67 @@ impl WinitHandler {
         Ok(())
     }
 
+    /// Process pending winit events, then return control to the caller instead of taking over the
+    /// thread like `.run()` does. `timeout` bounds how long to wait for an event to arrive; `None`
+    /// waits indefinitely for at least one. Lets an outer loop (a game clock, a headless test
+    /// harness, another UI) step the windowing layer frame-by-frame. The underlying `EventLoop` is
+    /// created on first use and kept around for subsequent calls.
+    pub fn pump(&mut self, timeout: Option<std::time::Duration>) -> PumpStatus {
+        use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus as WinitPumpStatus};
+
+        let mut event_loop = self
+            .event_loop
+            .take()
+            .unwrap_or_else(|| winit::event_loop::EventLoop::new().expect("failed to create event loop"));
+        let status = event_loop.pump_app_events(timeout, self);
+        self.event_loop = Some(event_loop);
+
+        match status {
+            WinitPumpStatus::Continue => PumpStatus::Continue,
+            WinitPumpStatus::Exit(code) => PumpStatus::Exit(code),
+        }
+    }
+
+    /// Drive the windowing layer to completion by calling [`Self::pump`] in a loop with no
+    /// timeout, and return the app's exit code. Like `.run()`, it does not return before the app
+    /// exits; unlike `.run()`, it uses the event loop kept by [`Self::pump`] instead of creating
+    /// and consuming its own.
+    pub fn run_on_demand(&mut self) -> i32 {
+        loop {
+            if let PumpStatus::Exit(code) = self.pump(None) {
+                return code;
+            }
+        }
+    }
+
     /// Set to true if your app has something special to do at every tick even if there are no user
     /// events. This can be used if you have physics or an animation to run. Defaults to false to
     /// preserve performance.
     pub fn set_always_tick(&mut self, val: bool) {
         self.always_tick = val;
     }
+
+    /// Register the bindings pressed keys should resolve to. Call this before `.run()`; it has no
+    /// effect afterwards since the keymap is handed off to the window on creation.
+    pub fn set_keymap(&mut self, keymap: KeyMap<Action>) {
+        self.keymap = keymap;
+    }
+
+    /// Current HiDPI scale factor of the window, or `1.0` before the window has been created.
+    pub fn scale_factor(&self) -> f64 {
+        self.winfbx.as_ref().map_or(1.0, |w| w.scale_factor)
+    }
 }
 
-impl winit::application::ApplicationHandler for WinitHandler {
+impl<Action: Eq + std::hash::Hash + Clone + From<Key> + 'static> winit::application::ApplicationHandler
+    for WinitHandler<Action>
+{
     /// Resume gets called when window gets loaded for the first time
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         log::info!(".resumed() called, creating window");
         if let Some(app) = self.app.take() {
-            self.winfbx = Some(WinFbx::new(event_loop, self.width, self.height, app));
+            let keymap = std::mem::replace(&mut self.keymap, KeyMap::new());
+            self.winfbx = Some(WinFbx::new(event_loop, self.width, self.height, app, keymap));
         }
     }
 
@@ -142,7 +171,7 @@ impl winit::application::ApplicationHandler for WinitHandler {
             app.on_tick();
             app.window.request_redraw();
         } else {
-            if self.always_tick || !app.pressed_keys.is_empty() {
+            if self.always_tick || !app.pressed_actions.is_empty() {
                 let duration_to_next_tick = self.tick - duration_from_last_tick;
                 event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(
                     now + duration_to_next_tick,
@@ -179,20 +208,25 @@ impl winit::application::ApplicationHandler for WinitHandler {
             WindowEvent::CursorMoved {
                 device_id: _,
                 position,
+            } => app.process_cursor_moved(position),
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                inner_size_writer: _,
             } => {
-                self.cursor_pos = (position.x, position.y);
+                let new_size = app.window.inner_size();
+                app.process_scale_factor_change(scale_factor, new_size);
             }
             WindowEvent::MouseInput {
                 device_id: _,
                 state,
-                button: _,
-            } if state.is_pressed() => {
-                log::info!(
-                    "clicked at x: {}, y: {}",
-                    self.cursor_pos.0,
-                    self.cursor_pos.1
-                )
-            }
+                button,
+            } => app.process_mouse_input(button, state),
+            WindowEvent::MouseWheel {
+                device_id: _,
+                delta,
+                phase: _,
+                ..
+            } => app.process_scroll(delta),
             _ => {}
         }
     }
@@ -204,31 +238,101 @@ pub fn put_pixel(frame: &mut [u8], width: usize, x: usize, y: usize, color: rgb:
     frame.as_rgba_mut()[idx] = color;
 }
 
-/// Manages the actual winit::Window, the Pixels, handles resizes, records pressed keys into a
-/// custom structure and call the given app tick and draw methods.
-struct WinFbx {
+/// Convert a logical-pixel coordinate (DPI-independent, what the app should reason in) to the
+/// physical coordinate backing the pixel buffer, given the window's current `scale_factor`.
+pub fn logical_to_physical(pos: (f64, f64), scale_factor: f64) -> (f64, f64) {
+    (pos.0 * scale_factor, pos.1 * scale_factor)
+}
+
+/// Convert a physical-pixel coordinate, such as the one carried by winit's `CursorMoved`, back to
+/// logical pixels using the window's current `scale_factor`.
+pub fn physical_to_logical(pos: (f64, f64), scale_factor: f64) -> (f64, f64) {
+    (pos.0 / scale_factor, pos.1 / scale_factor)
+}
+
+/// Like [`put_pixel`], but `x`/`y` are given in logical pixels and get scaled to the physical
+/// buffer resolution first. Useful on HiDPI displays where the app thinks in logical units.
+pub fn put_pixel_logical(
+    frame: &mut [u8],
+    width: usize,
+    x: f64,
+    y: f64,
+    scale_factor: f64,
+    color: rgb::RGBA8,
+) {
+    let (x, y) = logical_to_physical((x, y), scale_factor);
+    put_pixel(frame, width, x as usize, y as usize, color);
+}
+
+/// A handle apps get in [`GfxApp::on_tick`] to control the mouse cursor: its icon, visibility, and
+/// whether it's grabbed/confined to the window. Borrows the real `winit::window::Window` for the
+/// duration of the tick, so it can't outlive the call.
+pub struct WindowHandle<'a> {
+    window: &'a Window,
+}
+
+impl<'a> WindowHandle<'a> {
+    /// Set the cursor icon from winit's cross-platform set (`Default`, `Pointer` for a hand,
+    /// `Crosshair`, `Text`, the `*Resize` variants, etc.). Has no effect while the cursor is
+    /// hidden with [`Self::set_cursor_visible`].
+    pub fn set_cursor_icon(&self, icon: winit::window::CursorIcon) {
+        self.window.set_cursor(icon);
+    }
+
+    /// Show or hide the cursor while it's over the window.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.window.set_cursor_visible(visible);
+    }
+
+    /// Grab or confine the cursor to the window, e.g. for mouse-look. Falls back from `mode`
+    /// through the other grab modes down to `CursorGrabMode::None` when the platform doesn't
+    /// support the requested one, logging a warning if even that fails.
+    pub fn set_cursor_grab(&self, mode: winit::window::CursorGrabMode) {
+        use winit::window::CursorGrabMode;
+        for candidate in [mode, CursorGrabMode::Locked, CursorGrabMode::Confined, CursorGrabMode::None] {
+            match self.window.set_cursor_grab(candidate) {
+                Ok(()) => return,
+                Err(_) if candidate == CursorGrabMode::None => {
+                    log::warn!("failed to release cursor grab");
+                    return;
+                }
+                Err(_) => continue,
+            }
+        }
+    }
+}
+
+/// Manages the actual winit::Window, the Pixels, handles resizes, resolves pressed keys through
+/// the app's `KeyMap` and calls the given app tick and draw methods.
+struct WinFbx<Action: Eq + std::hash::Hash + Clone + From<Key>> {
     window: Window,
     pixels: Pixels,
     pause: bool,
     height: usize,
     width: usize,
-    pressed_keys: std::collections::HashSet<MyKeys>,
-    released_keys: std::collections::HashSet<MyKeys>,
+    keymap: KeyMap<Action>,
+    pressed_actions: std::collections::HashSet<Action>,
+    released_actions: std::collections::HashSet<Action>,
+    cursor_pos: (f64, f64),
     needs_render: bool,
-    app: Box<dyn GfxApp>,
+    app: Box<dyn GfxApp<Action>>,
+    /// HiDPI scale factor reported by winit for the monitor the window currently lives on.
+    scale_factor: f64,
 }
 
-impl WinFbx {
+impl<Action: Eq + std::hash::Hash + Clone + From<Key>> WinFbx<Action> {
     fn new(
         event_loop: &winit::event_loop::ActiveEventLoop,
         width: usize,
         height: usize,
-        app: Box<dyn GfxApp>,
+        app: Box<dyn GfxApp<Action>>,
+        keymap: KeyMap<Action>,
     ) -> Self {
         let mut attr = Window::default_attributes();
         let size = winit::dpi::PhysicalSize::new(width as u16, height as u16);
         attr = attr.with_inner_size(size).with_title("Box");
         let win = event_loop.create_window(attr).unwrap();
+        let scale_factor = win.scale_factor();
 
         let mut pixels = {
             let surface_texture = SurfaceTexture::new(width as u32, height as u32, &win);
@@ -246,10 +350,13 @@ impl WinFbx {
             height,
             width,
             pause: false,
-            pressed_keys: std::collections::HashSet::new(),
-            released_keys: std::collections::HashSet::new(),
+            keymap,
+            pressed_actions: std::collections::HashSet::new(),
+            released_actions: std::collections::HashSet::new(),
+            cursor_pos: (0.0, 0.0),
             needs_render: true,
             app,
+            scale_factor,
         }
     }
 
@@ -271,11 +378,14 @@ impl WinFbx {
 
     fn on_tick(&mut self) {
         if self.app.done() == DoneStatus::NotDone {
-            self.needs_render = self.app.on_tick(&self.pressed_keys);
+            let mut window = WindowHandle {
+                window: &self.window,
+            };
+            self.needs_render = self.app.on_tick(&self.pressed_actions, &mut window);
         }
-        self.pressed_keys
-            .retain(|candidate| !self.released_keys.contains(candidate));
-        self.released_keys.clear();
+        self.pressed_actions
+            .retain(|candidate| !self.released_actions.contains(candidate));
+        self.released_actions.clear();
     }
 
     fn process_kbd_input(
@@ -283,25 +393,51 @@ impl WinFbx {
         event: winit::event::KeyEvent,
         event_loop: &winit::event_loop::ActiveEventLoop,
     ) {
-        use winit::keyboard::{Key, NamedKey};
-        if let Ok(my_key) = (&event.logical_key).try_into() {
-            if event.state == winit::event::ElementState::Pressed {
-                self.pressed_keys.insert(my_key);
-            } else if event.state == winit::event::ElementState::Released {
-                self.released_keys.insert(my_key);
+        if let Ok(key) = Key::try_from(event.physical_key) {
+            let action = self.keymap.resolve(key);
+            match event.state {
+                winit::event::ElementState::Pressed => {
+                    self.pressed_actions.insert(action);
+                }
+                winit::event::ElementState::Released => {
+                    self.released_actions.insert(action);
+                }
             }
-        };
-        if event.state == winit::event::ElementState::Pressed {
-            match event.logical_key {
-                Key::Named(NamedKey::Escape) => event_loop.exit(),
-                Key::Named(NamedKey::Space) => {
-                    self.pause = !self.pause;
+            if event.state == winit::event::ElementState::Pressed {
+                match key {
+                    Key::Escape => event_loop.exit(),
+                    Key::Space => self.pause = !self.pause,
+                    _ => {}
                 }
-                _ => {}
             }
         }
     }
 
+    fn process_cursor_moved(&mut self, position: winit::dpi::PhysicalPosition<f64>) {
+        self.cursor_pos = (position.x, position.y);
+        self.needs_render |= self.app.on_pointer_move(position.x, position.y);
+    }
+
+    fn process_mouse_input(
+        &mut self,
+        button: winit::event::MouseButton,
+        state: winit::event::ElementState,
+    ) {
+        let pressed = state.is_pressed();
+        self.needs_render |=
+            self.app
+                .on_pointer_button(button, pressed, self.cursor_pos.0, self.cursor_pos.1);
+    }
+
+    fn process_scroll(&mut self, delta: winit::event::MouseScrollDelta) {
+        use winit::event::MouseScrollDelta;
+        let (delta_x, delta_y) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (x, y),
+            MouseScrollDelta::PixelDelta(pos) => (pos.x as f32, pos.y as f32),
+        };
+        self.needs_render |= self.app.on_scroll(delta_x, delta_y);
+    }
+
     fn process_resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
         self.width = size.width as usize;
         self.height = size.height as usize;
@@ -310,6 +446,20 @@ impl WinFbx {
         self.window.request_redraw();
         self.needs_render = true;
     }
+
+    /// The window moved to a monitor with a different HiDPI factor (or the OS setting changed).
+    /// The pixel buffer is resized to the new physical surface size right away, instead of waiting
+    /// for a follow-up `Resized` event, and the app is notified so it can refresh any cached
+    /// logical/physical conversions.
+    fn process_scale_factor_change(
+        &mut self,
+        scale_factor: f64,
+        new_size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        self.scale_factor = scale_factor;
+        self.process_resize(new_size);
+        self.app.on_scale_factor_changed(scale_factor);
+    }
 }
 
 #[derive(Eq, PartialEq)]
@@ -324,9 +474,17 @@ pub enum DoneStatus {
     NotDone,
 }
 
-pub trait GfxApp {
-    /// Every tick, this method gets called with currently pressed keys. Released keys during the tick are considered still pressed. But will be removed after this call.
-    fn on_tick(&mut self, pressed_keys: &std::collections::HashSet<MyKeys>) -> bool;
+/// Implement this for your game/tool. `Action` is whatever your app wants to react to on each
+/// tick; it defaults to [`Key`] (the raw, layout-independent physical key), but can be a custom
+/// enum bound through a [`KeyMap`] so the same app works with remappable, named actions instead of
+/// raw keys.
+pub trait GfxApp<Action = Key> {
+    /// Every tick, this method gets called with currently pressed actions. Released actions during the tick are considered still pressed. But will be removed after this call. `window` lets the app control the cursor icon, visibility, and grab mode.
+    fn on_tick(
+        &mut self,
+        pressed_actions: &std::collections::HashSet<Action>,
+        window: &mut WindowHandle,
+    ) -> bool;
 
     /// You get the pixel array, so you can draw on it before the render.
     fn draw(&self, pixels: &mut Pixels, width: usize);
@@ -334,4 +492,39 @@ pub trait GfxApp {
     /// Indicate if the app logic is done and if the program should remain or exit. For oneshot
     /// drawing, return `DoneStatus::Remain` so that the result stays on screen.
     fn done(&self) -> DoneStatus;
+
+    /// Called when the window's HiDPI scale factor changes, e.g. when it is dragged to a
+    /// different-DPI monitor. The pixel buffer has already been resized to the new physical
+    /// surface size by the time this is called. Apps that cache logical/physical conversions
+    /// (see [`logical_to_physical`] and [`physical_to_logical`]) should refresh them here.
+    /// Does nothing by default.
+    fn on_scale_factor_changed(&mut self, _scale_factor: f64) {}
+
+    /// Called whenever the cursor moves over the window, with its position in physical pixels.
+    /// Returns whether the app needs a redraw, same convention as `on_tick`. Does nothing by
+    /// default.
+    fn on_pointer_move(&mut self, _x: f64, _y: f64) -> bool {
+        false
+    }
+
+    /// Called when a mouse button is pressed or released, with the cursor's position (in physical
+    /// pixels) at the time of the event. Returns whether the app needs a redraw, same convention
+    /// as `on_tick`. Does nothing by default.
+    fn on_pointer_button(
+        &mut self,
+        _button: winit::event::MouseButton,
+        _pressed: bool,
+        _x: f64,
+        _y: f64,
+    ) -> bool {
+        false
+    }
+
+    /// Called on scroll wheel/trackpad input. `delta_x`/`delta_y` follow winit's
+    /// `MouseScrollDelta::LineDelta` convention when the device reports lines, or raw pixels
+    /// otherwise. Returns whether the app needs a redraw, same convention as `on_tick`. Does
+    /// nothing by default.
+    fn on_scroll(&mut self, _delta_x: f32, _delta_y: f32) -> bool {
+        false
+    }
 }
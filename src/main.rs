@@ -1,4 +1,4 @@
-use chinchilib::{put_pixel1, GfxApp, MyKeys, WinitHandler};
+use chinchilib::{put_pixel, GfxApp, Key, WindowHandle, WinitHandler};
 use pixels::Pixels;
 
 struct MovingPixel {
@@ -17,32 +17,31 @@ impl MovingPixel {
     }
 }
 impl GfxApp for MovingPixel {
-    fn on_tick(&mut self, pressed_keys: &std::collections::HashSet<MyKeys>) -> bool {
+    fn on_tick(
+        &mut self,
+        pressed_keys: &std::collections::HashSet<Key>,
+        _window: &mut WindowHandle,
+    ) -> bool {
         let mut ret = false;
         for key in pressed_keys {
             match key {
-                MyKeys::Left => {
+                Key::Left => {
                     self.pos.0 -= 1;
                     ret = true;
                 }
-                MyKeys::KeyQ => {}
-                MyKeys::Right => {
+                Key::Right => {
                     self.pos.0 += 1;
                     ret = true;
                 }
-                MyKeys::KeyD => {}
-                MyKeys::Up => {
+                Key::Up => {
                     self.pos.1 -= 1;
                     ret = true;
                 }
-                MyKeys::KeyZ => {}
-                MyKeys::Down => {
+                Key::Down => {
                     self.pos.1 += 1;
                     ret = true;
                 }
-                MyKeys::KeyS => {}
-                MyKeys::KeyA => {}
-                MyKeys::KeyE => {}
+                _ => {}
             }
         }
         ret
@@ -50,7 +49,7 @@ impl GfxApp for MovingPixel {
 
     fn draw(&self, pixels: &mut Pixels, width: usize) {
         if self.pos.0 * self.pos.1 < pixels.frame().len() {
-            put_pixel1(
+            put_pixel(
                 pixels.frame_mut(),
                 width,
                 self.pos.0,
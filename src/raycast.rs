@@ -1,6 +1,9 @@
 use std::usize;
 
 use array2d::Array2D;
+use pixels::Pixels;
+
+use crate::put_pixel;
 
 pub struct World {
     map: Array2D<u8>,
@@ -46,22 +49,90 @@ pub enum Heading {
     Left,
 }
 
+/// Which gridline a ray last crossed. Walls hit on a y-gridline are shaded darker, which is what
+/// gives the classic raycaster look of depth between perpendicular walls.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    X,
+    Y,
+}
+
+/// Result of casting a single ray into the map with [`World::cast_ray`].
+struct RayHit {
+    /// Distance to the wall, measured perpendicular to the player's heading rather than along the
+    /// ray itself. This is what avoids the fisheye distortion a plain Euclidean distance produces.
+    perp_dist: f32,
+    side: Side,
+}
+
 impl World {
     fn is_wall(&self, coords: (f32, f32)) -> bool {
-        let coords = (coords.0 as usize, coords.1 as usize);
-        coords.0 >= self.map.row_len()
-            || coords.1 >= self.map.column_len()
-            || self.map[coords] == 'X' as u8
+        // Bounds-check on the signed coordinate first: casting a negative float straight to
+        // `usize` saturates at 0 instead of wrapping, which would otherwise make `(-1, 0)` look
+        // like the in-bounds `(0, 0)` and let a ray walk off the west/north edge of the map
+        // forever on any map that isn't fully bordered by walls.
+        let (x, y) = (coords.0 as i32, coords.1 as i32);
+        if x < 0 || y < 0 {
+            return true;
+        }
+        let (x, y) = (x as usize, y as usize);
+        x >= self.map.row_len() || y >= self.map.column_len() || self.map[(x, y)] == 'X' as u8
+    }
+
+    /// Cast a single ray in the given absolute direction using DDA (digital differential
+    /// analysis) grid traversal: step from gridline to gridline instead of marching in small fixed
+    /// increments, which is both exact (no resolution limit) and far cheaper than the old
+    /// `distance += 0.01` search.
+    fn cast_ray(&self, direction: f32) -> RayHit {
+        let ray_dir_x = direction.cos();
+        let ray_dir_y = direction.sin();
+
+        let mut map_x = self.player_pos.0 as i32;
+        let mut map_y = self.player_pos.1 as i32;
+
+        let delta_dist_x = (1.0 / ray_dir_x).abs();
+        let delta_dist_y = (1.0 / ray_dir_y).abs();
+
+        let (step_x, mut side_dist_x) = if ray_dir_x < 0.0 {
+            (-1, (self.player_pos.0 - map_x as f32) * delta_dist_x)
+        } else {
+            (1, (map_x as f32 + 1.0 - self.player_pos.0) * delta_dist_x)
+        };
+        let (step_y, mut side_dist_y) = if ray_dir_y < 0.0 {
+            (-1, (self.player_pos.1 - map_y as f32) * delta_dist_y)
+        } else {
+            (1, (map_y as f32 + 1.0 - self.player_pos.1) * delta_dist_y)
+        };
+
+        let side = loop {
+            let side = if side_dist_x < side_dist_y {
+                map_x += step_x;
+                side_dist_x += delta_dist_x;
+                Side::X
+            } else {
+                map_y += step_y;
+                side_dist_y += delta_dist_y;
+                Side::Y
+            };
+            if self.is_wall((map_x as f32, map_y as f32)) {
+                break side;
+            }
+        };
+
+        // The side that was just stepped overshot the wall by one `delta_dist`, so backing that
+        // off gives the distance along the ray without re-walking it. Projecting that onto the
+        // player's heading then gives the perpendicular distance.
+        let ray_dist = match side {
+            Side::X => side_dist_x - delta_dist_x,
+            Side::Y => side_dist_y - delta_dist_y,
+        };
+        let perp_dist = ray_dist * (direction - self.player_heading).cos().abs();
+
+        RayHit { perp_dist, side }
     }
 
     fn distance_to_wall(&self, heading: f32) -> f32 {
-        let mut distance: f32 = 0.0;
-        let mut coords = move_forward(self.player_pos, heading, distance);
-        while !self.is_wall(coords) {
-            distance += 0.01;
-            coords = move_forward(self.player_pos, heading, distance);
-        }
-        return distance;
+        self.cast_ray(heading).perp_dist
     }
 
     pub fn distance_to_walls<'a>(&'a self, ray_quantity: usize) -> impl Iterator<Item = f32> + 'a {
@@ -69,6 +140,38 @@ impl World {
             .map(|angle| self.distance_to_wall(angle + self.player_heading))
     }
 
+    /// Draw a first-person view of the map: for each screen column, cast a ray and draw the
+    /// corresponding vertical wall slice, shaded by distance and darkened on y-sides so
+    /// perpendicular walls read as distinct surfaces.
+    pub fn render(&self, pixels: &mut Pixels, width: usize, height: usize) {
+        let frame = pixels.frame_mut();
+        for (column, angle) in generate_ray_angles(width, self.player_fov).enumerate() {
+            let hit = self.cast_ray(angle + self.player_heading);
+            let perp_dist = hit.perp_dist.max(0.0001);
+            let line_height = (height as f32 / perp_dist) as i32;
+
+            let half_height = height as i32 / 2;
+            let draw_start = (half_height - line_height / 2).max(0) as usize;
+            let draw_end = ((half_height + line_height / 2).max(0) as usize).min(height - 1);
+
+            let base_shade: f32 = match hit.side {
+                Side::X => 255.0,
+                Side::Y => 180.0,
+            };
+            let shade = (base_shade / (1.0 + perp_dist * 0.2)) as u8;
+            let color = rgb::RGBA8 {
+                r: shade,
+                g: shade,
+                b: shade,
+                a: 255,
+            };
+
+            for row in draw_start..=draw_end {
+                put_pixel(frame, width, column, row, color);
+            }
+        }
+    }
+
     pub fn pan_left(&mut self) {
         self.player_heading -= std::f32::consts::FRAC_PI_8;
         log::debug!("heading {}", rads_to_deg(self.player_heading));
@@ -196,4 +299,44 @@ mod test {
         assert!(res[3] > 1.99);
         assert!(res[3] < 2.01);
     }
+
+    #[test]
+    fn cast_ray_known_distance() {
+        // 3x3 map walled on every border, open interior, player centered and facing east.
+        let mut map = Array2D::filled_with(' ' as u8, 3, 3);
+        for i in 0..3 {
+            map[(i, 0)] = 'X' as u8;
+            map[(i, 2)] = 'X' as u8;
+            map[(0, i)] = 'X' as u8;
+            map[(2, i)] = 'X' as u8;
+        }
+        let world = World {
+            map,
+            player_pos: (1.5, 1.5),
+            player_heading: 0.0,
+            player_fov: degs_to_rads(70),
+        };
+
+        let hit = world.cast_ray(0.0);
+        assert!((hit.perp_dist - 0.5).abs() < 0.001, "{}", hit.perp_dist);
+        assert!(hit.side == Side::X);
+    }
+
+    #[test]
+    fn cast_ray_terminates_on_unbordered_map() {
+        // No walls at all: a ray cast west must still terminate by treating the map edge as a
+        // wall, instead of stepping `map_x` to -1, -2, ... forever. Before `is_wall` bounds-checked
+        // the signed coordinate, `coords.0 as usize` saturated negative `map_x` to 0 and this test
+        // hung indefinitely.
+        let map = Array2D::filled_with(' ' as u8, 3, 3);
+        let world = World {
+            map,
+            player_pos: (1.5, 1.5),
+            player_heading: 0.0,
+            player_fov: degs_to_rads(70),
+        };
+
+        let hit = world.cast_ray(std::f32::consts::PI);
+        assert!((hit.perp_dist - 1.5).abs() < 0.001, "{}", hit.perp_dist);
+    }
 }